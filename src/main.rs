@@ -1,19 +1,34 @@
 use std::io;
 use std::ops;
 use std::collections::HashMap;
-use rand::Rng;
+use std::collections::HashSet;
+use rand::rngs::StdRng;
+use rand::{FromEntropy, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
 struct TerminalInfo {
     height: u16,
     width: u16,
 }
 
+// Which policy a CPU seat uses to pick a rank to ask for. Kept around so
+// simulation mode can pit strategies against each other.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+enum CpuStrategy {
+    // Always asks for the rank held the most, ignoring anything observed.
+    Naive,
+    // Uses Knowledge of ranks known to still be held by an opponent.
+    Knowledge,
+}
+
 #[derive(Eq, Hash, Copy, Clone, PartialEq)]
 enum CardRank {
     Heart,
     Diamond,
     Spade,
     Clover,
+    // A joker's suit; jokers carry no face value and sort last.
+    Joker,
 }
 
 impl CardRank {
@@ -23,6 +38,7 @@ impl CardRank {
             CardRank::Diamond => String::from("♦"),
             CardRank::Spade => String::from("♠"),
             CardRank::Clover => String::from("♣"),
+            CardRank::Joker => String::from("🃏"),
         }
     }
 
@@ -32,15 +48,27 @@ impl CardRank {
             CardRank::Diamond => 2,
             CardRank::Spade => 3,
             CardRank::Clover => 4,
+            CardRank::Joker => 5,
         }
     }
 }
 
+// Which deck a game is dealt from. Simulation mode takes it too, playing
+// every game of a batch on the chosen deck.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+enum DeckConfig {
+    Standard,
+    // Adds two jokers that act as wildcards completing a book of three-of-a-kind.
+    Jokers,
+}
+
 struct Player {
     name: String,
     is_cpu: bool,
     cards: Vec<Card>,
     books: Vec<Book>,
+    knowledge: Knowledge,
+    cpu_strategy: CpuStrategy,
 }
 
 impl Player {
@@ -50,15 +78,23 @@ impl Player {
             is_cpu: false,
             cards: Vec::with_capacity(52),
             books: Vec::with_capacity(13),
+            knowledge: Knowledge::new(),
+            cpu_strategy: CpuStrategy::Knowledge,
         }
     }
 
-    fn new_cpu() -> Player {
+    fn new_cpu(name: &str) -> Player {
+        Player::new_cpu_with_strategy(name, CpuStrategy::Knowledge)
+    }
+
+    fn new_cpu_with_strategy(name: &str, cpu_strategy: CpuStrategy) -> Player {
         Player {
-            name: String::from("Computer"),
+            name: String::from(name),
             is_cpu: true,
             cards: Vec::with_capacity(52),
             books: Vec::with_capacity(13),
+            knowledge: Knowledge::new(),
+            cpu_strategy,
         }
     }
 
@@ -74,8 +110,42 @@ impl Player {
     }
 }
 
+// Tracks, per opponent seat, which ranks that opponent is known to still
+// hold. A rank becomes known-held the moment a player asks for it (asking
+// proves you hold at least one at that moment) and stays known-held until
+// it provably leaves that player's hand again, either because all four
+// copies were collected into a book or because they were handed over in a
+// transfer.
+struct Knowledge {
+    known_held: HashMap<usize, HashSet<u8>>,
+}
+
+impl Knowledge {
+    fn new() -> Knowledge {
+        Knowledge {
+            known_held: HashMap::new(),
+        }
+    }
+
+    fn record_request(&mut self, player_index: usize, rank: u8) {
+        self.known_held.entry(player_index).or_insert_with(HashSet::new).insert(rank);
+    }
+
+    fn clear_rank_for_player(&mut self, player_index: usize, rank: u8) {
+        if let Some(ranks) = self.known_held.get_mut(&player_index) {
+            ranks.remove(&rank);
+        }
+    }
+
+    fn holds_rank(&self, player_index: usize, rank: u8) -> bool {
+        self.known_held.get(&player_index).map_or(false, |ranks| ranks.contains(&rank))
+    }
+}
+
 struct Book {
     number: u8,
+    // Set when a joker stood in for the fourth card of this rank.
+    used_joker: bool,
 }
 
 #[derive(Copy, Clone)]
@@ -85,7 +155,15 @@ struct Card {
 }
 
 impl Card {
+    fn is_joker(&self) -> bool {
+        self.rank == CardRank::Joker
+    }
+
     fn get_label(&self) -> String {
+        if self.is_joker() {
+            return self.rank.get_label();
+        }
+
         let num_key = match self.number {
             11 => String::from("J"),
             12 => String::from("Q"),
@@ -100,8 +178,21 @@ impl Card {
     }
 }
 
+// A single structured, replayable step of a game. Unlike the human-readable
+// status lines, these carry enough information to reconstruct state.
+#[derive(Clone, Serialize, Deserialize)]
+enum GameEvent {
+    Request { asker: String, target: String, rank: u8 },
+    Transfer { from: String, to: String, card: String },
+    GoFish { player: String, drew: Option<String> },
+    BookFormed { player: String, rank: u8, used_joker: bool },
+    TurnEnded,
+    GameOver { winner: String },
+}
+
 struct GameLog {
     status_lines: Vec<String>,
+    events: Vec<GameEvent>,
     turn: usize,
 }
 
@@ -110,6 +201,7 @@ impl GameLog {
         GameLog {
             turn: 1,
             status_lines: vec![],
+            events: vec![],
         }
     }
 
@@ -122,12 +214,178 @@ impl GameLog {
         message_formatted.push_str(&message);
         self.status_lines.push(message_formatted);
     }
+
+    fn add_event(&mut self, event: GameEvent) {
+        self.events.push(event);
+    }
+}
+
+// Enough to recreate a seat when reconstructing a game for replay.
+#[derive(Clone, Serialize, Deserialize)]
+struct PlayerInfo {
+    name: String,
+    is_cpu: bool,
+    cpu_strategy: Option<CpuStrategy>,
+}
+
+impl PlayerInfo {
+    fn from_player(player: &Player) -> PlayerInfo {
+        PlayerInfo {
+            name: player.name.clone(),
+            is_cpu: player.is_cpu,
+            cpu_strategy: if player.is_cpu { Some(player.cpu_strategy) } else { None },
+        }
+    }
+
+    fn to_player(&self) -> Player {
+        if self.is_cpu {
+            Player::new_cpu_with_strategy(&self.name, self.cpu_strategy.unwrap_or(CpuStrategy::Knowledge))
+        } else {
+            Player::new(&self.name)
+        }
+    }
+}
+
+// A full, replayable record of one game: everything needed to re-drive
+// `run_game` with the same seed and roster and land on the same outcome.
+#[derive(Serialize, Deserialize)]
+struct GameTranscript {
+    seed: u64,
+    deck: DeckConfig,
+    players: Vec<PlayerInfo>,
+    events: Vec<GameEvent>,
+    final_standings: Vec<(String, usize)>,
+    winner: String,
+}
+
+// Decouples prompts from stdin so a move script can drive the game in
+// tests without a live terminal.
+trait InputSource {
+    // The next token, or None at end of input. A script yields one
+    // whitespace-separated word per call; the terminal yields one trimmed line.
+    fn next_token(&mut self) -> Option<String>;
+
+    // Whether the next call is already known to return None. Terminal
+    // input can't know this ahead of a read, so it always reports false.
+    fn is_exhausted(&self) -> bool {
+        false
+    }
+}
+
+struct TerminalInput;
+
+impl InputSource for TerminalInput {
+    fn next_token(&mut self) -> Option<String> {
+        let mut line = String::new();
+        match io::stdin().read_line(&mut line) {
+            Ok(0) => None,
+            Ok(_) => Some(line.trim().to_string()),
+            Err(_) => None,
+        }
+    }
+}
+
+struct ScriptInput {
+    tokens: std::collections::VecDeque<String>,
+}
+
+impl ScriptInput {
+    fn from_file(path: &str) -> ScriptInput {
+        let contents = std::fs::read_to_string(path).expect("Unable to read script file");
+        let tokens = contents.split_whitespace().map(String::from).collect();
+        ScriptInput { tokens }
+    }
+}
+
+impl InputSource for ScriptInput {
+    fn next_token(&mut self) -> Option<String> {
+        self.tokens.pop_front()
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.tokens.is_empty()
+    }
+}
+
+// Command-line flags the game understands. Anything not recognized is
+// ignored so the binary keeps working with no arguments at all.
+struct CliOptions {
+    simulate: Option<usize>,
+    seed: Option<u64>,
+    json_out: Option<String>,
+    replay: Option<String>,
+    script: Option<String>,
+    deck: Option<DeckConfig>,
+}
+
+fn parse_cli_options() -> CliOptions {
+    let args: Vec<String> = std::env::args().collect();
+
+    let mut simulate = None;
+    let mut seed = None;
+    let mut json_out = None;
+    let mut replay = None;
+    let mut script = None;
+    let mut deck = None;
+    let mut index = 1;
+
+    while index < args.len() {
+        match args[index].as_str() {
+            "--simulate" => {
+                index += 1;
+                simulate = args.get(index).and_then(|value| value.parse().ok());
+            }
+            "--seed" => {
+                index += 1;
+                seed = args.get(index).and_then(|value| value.parse().ok());
+            }
+            "--json-out" => {
+                index += 1;
+                json_out = args.get(index).cloned();
+            }
+            "--replay" => {
+                index += 1;
+                replay = args.get(index).cloned();
+            }
+            "--script" => {
+                index += 1;
+                script = args.get(index).cloned();
+            }
+            "--deck" => {
+                index += 1;
+                deck = args.get(index).and_then(|value| match value.as_str() {
+                    "standard" => Some(DeckConfig::Standard),
+                    "jokers" => Some(DeckConfig::Jokers),
+                    _ => None,
+                });
+            }
+            _ => (),
+        }
+        index += 1;
+    }
+
+    CliOptions { simulate, seed, json_out, replay, script, deck }
 }
 
 fn main() {
+    let options = parse_cli_options();
+
+    if let Some(replay_path) = options.replay {
+        run_replay(&replay_path);
+        return;
+    }
+
+    if let Some(num_games) = options.simulate {
+        run_simulation(num_games, options.seed, options.deck.unwrap_or(DeckConfig::Standard));
+        return;
+    }
+
     // get info in seperate scope.
     let terminal_info = {
-        let (terminal_width, terminal_height) = termion::terminal_size().unwrap();
+        // Querying the terminal fails when stdout isn't a TTY (piped output,
+        // --script driven integration tests). Fall back to a sane default
+        // rather than panicking before a single scripted token is read.
+        let (terminal_width, terminal_height) = termion::terminal_size().unwrap_or((80, 24));
 
         TerminalInfo {
             width: terminal_width,
@@ -135,150 +393,408 @@ fn main() {
         }
     };
 
+    // A concrete seed is recorded up front so --json-out can always produce
+    // a transcript that --replay can later reproduce exactly.
+    let seed = options.seed.unwrap_or_else(|| StdRng::from_entropy().gen());
+    let mut rng = make_rng(Some(seed));
+
     // clear screen on init.
     println!("{}", termion::clear::All);
 
-    let mut deck = generate_deck();
-    assert_eq!(52, deck.len());
+    let mut input: Box<dyn InputSource> = match &options.script {
+        Some(script_path) => Box::new(ScriptInput::from_file(script_path)),
+        None => Box::new(TerminalInput),
+    };
 
     // Welcome:
     println!("Welcome to Go Fish");
     println!("Please enter your name:");
 
-    let player_name = {
-        let mut temp_string = String::new();
-        io::stdin().read_line(&mut temp_string).expect("Unable to read your name");
-        temp_string.trim().to_string()
-    };
+    let player_name = input.next_token().unwrap_or_default();
+
+    println!("How many players (3-6)?");
+    let num_players: u8 = input
+        .next_token()
+        .and_then(|token| token.parse().ok())
+        .unwrap_or(3)
+        .clamp(3, 6);
+
+    let deck_config = options.deck.unwrap_or_else(|| {
+        println!("Play with jokers? (y/n)");
+        let answer = input.next_token().unwrap_or_default().to_lowercase();
+        if answer.starts_with('y') { DeckConfig::Jokers } else { DeckConfig::Standard }
+    });
+
+    // Create player instances: the human in seat 0, CPUs filling the rest.
+    let mut players: Vec<Player> = Vec::with_capacity(num_players as usize);
+    players.push(Player::new(&player_name));
+    for seat in 1..num_players {
+        players.push(Player::new_cpu(&format!("Computer {}", seat)));
+    }
 
-    // Create player instances.
-    let mut player = Player::new(&player_name);
-    let mut opponent = Player::new_cpu();
+    let roster: Vec<PlayerInfo> = players.iter().map(PlayerInfo::from_player).collect();
+
+    let result = run_game(
+        &mut rng,
+        players,
+        deck_config,
+        &mut *input,
+        &terminal_info,
+        |terminal_info, deck, game_log, players, current_turn_index| {
+            println!("{}{}Go Fish v0.3.0",
+                termion::clear::All,
+                termion::cursor::Goto(1, 1));
+
+            println!("{}{} cards in deck", termion::cursor::Goto(1, 2), deck.len());
+
+            let player_books_string: String = {
+                let mut standings: Vec<&Player> = players.iter().collect();
+                standings.sort_by(|a, b| {
+                    return a.books.len().cmp(&b.books.len()).reverse().then_with(|| a.name.cmp(&b.name));
+                });
+                let player_books_strings: Vec<String> = standings.into_iter().map(|this_player| format!("{} ({})", this_player.name, this_player.books.len())).collect();
+                format!("Standings: {}", player_books_strings.join(", "))
+            };
+            println!("{}{}", termion::cursor::Goto(1, 3), player_books_string);
 
-    give_random_card(&mut deck, &mut player, 7);
-    give_random_card(&mut deck, &mut opponent, 7);
+            let active_player_cards_string = players[current_turn_index].get_card_labels().join(", ");
+            println!("{}{}",
+                termion::cursor::Goto(1, terminal_info.height / 2),
+                active_player_cards_string);
+
+            // print status lines
+            for (pos, line) in game_log.status_lines.iter().enumerate() {
+                println!("{}{}",
+                    termion::cursor::Goto(1, terminal_info.height - 2 - (pos as u16)),
+                    line);
+            }
+        },
+    );
 
-    let mut game_log = GameLog::new();
+    if result.incomplete {
+        println!("{}Script ran out of moves, ending game early",
+            termion::cursor::Goto(1, terminal_info.height - 1));
+        return;
+    }
 
-    loop {
-        // create books.
-        check_cards_for_books(&mut game_log, &mut player);
-        check_cards_for_books(&mut game_log, &mut opponent);
+    let winner_books = result.standings.iter()
+        .find(|(name, _)| name == &result.winner_name)
+        .map(|&(_, books)| books)
+        .unwrap_or(0);
+    gameover(&result.winner_name, winner_books);
 
-        let mut game_standings: Vec<&Player> = vec![&player, &opponent];
-        game_standings.sort_by(|a, b| {
-            return a.books.len().cmp(&b.books.len()).reverse().then_with(|| a.name.cmp(&b.name));
-        });
+    if let Some(json_out_path) = &options.json_out {
+        write_transcript(json_out_path, seed, deck_config, roster, result.events, result.standings, result.winner_name);
+    }
+}
 
-        if let Some(winner) = determine_winner(&game_standings) {
-            gameover(&winner);
-            break;
+fn write_transcript(
+    path: &str,
+    seed: u64,
+    deck: DeckConfig,
+    players: Vec<PlayerInfo>,
+    events: Vec<GameEvent>,
+    final_standings: Vec<(String, usize)>,
+    winner: String,
+) {
+    let transcript = GameTranscript { seed, deck, players, events, final_standings, winner };
+    let json = serde_json::to_string_pretty(&transcript).expect("Unable to serialize game transcript");
+    std::fs::write(path, json).expect("Unable to write game transcript");
+}
+
+// Jokers are never requested by rank, so this only needs to cover 2-14,
+// the same vocabulary `get_player_requesting_card_value` accepts.
+fn rank_to_token(rank: u8) -> String {
+    match rank {
+        11 => String::from("J"),
+        12 => String::from("Q"),
+        13 => String::from("K"),
+        14 => String::from("A"),
+        _ => rank.to_string(),
+    }
+}
+
+// Rebuilds the (rank, target) tokens of every request a non-CPU seat made
+// during the recorded game (inputs `turn()` rejected leave no event and are
+// not rebuilt), so `run_game` can replay that seat without a
+// live terminal. `main` only ever records a single human seat, so only the
+// first one found is scripted; an all-CPU transcript yields an empty script
+// that's never read.
+fn build_replay_script(transcript: &GameTranscript) -> ScriptInput {
+    let mut tokens: std::collections::VecDeque<String> = std::collections::VecDeque::new();
+
+    if let Some(human_index) = transcript.players.iter().position(|player| !player.is_cpu) {
+        let human_name = &transcript.players[human_index].name;
+
+        for event in transcript.events.iter() {
+            if let GameEvent::Request { asker, target, rank } = event {
+                if asker != human_name {
+                    continue;
+                }
+
+                let target_index = transcript.players.iter().position(|player| &player.name == target).unwrap_or(0);
+                let choice = if target_index < human_index { target_index } else { target_index - 1 } + 1;
+
+                tokens.push_back(rank_to_token(*rank));
+                tokens.push_back(format!("target:{}", choice));
+            }
         }
+    }
 
-        println!("{}{}Go Fish v0.3.0",
-            termion::clear::All,
-            termion::cursor::Goto(1, 1));
+    ScriptInput { tokens }
+}
 
-        println!("{}{} cards in deck", termion::cursor::Goto(1, 2), deck.len());
+// Reconstructs a recorded game from its seed and roster, re-drives it
+// through `run_game`, and checks the outcome still matches what was
+// recorded, proving the transcript is a faithful, deterministic record.
+fn run_replay(path: &str) {
+    let contents = std::fs::read_to_string(path).expect("Unable to read replay file");
+    let transcript: GameTranscript = serde_json::from_str(&contents).expect("Invalid game transcript");
+
+    let players: Vec<Player> = transcript.players.iter().map(PlayerInfo::to_player).collect();
+    let mut rng = make_rng(Some(transcript.seed));
+    let mut input = build_replay_script(&transcript);
+    let terminal_info = TerminalInfo { height: 24, width: 80 };
+    let result = run_game(&mut rng, players, transcript.deck, &mut input, &terminal_info, |_, _, _, _, _| {});
+
+    if result.winner_name == transcript.winner && result.standings == transcript.final_standings {
+        println!("Replay OK: seed {} reproduced the recorded standings", transcript.seed);
+    } else {
+        println!("Replay MISMATCH for seed {}", transcript.seed);
+        println!("Recorded: {:?}", transcript.final_standings);
+        println!("Replayed: {:?}", result.standings);
+    }
+}
 
-        let player_books_string: String = {
-            let player_books_strings: Vec<String> = game_standings.into_iter().map(|this_player| format!("{} ({})", this_player.name, this_player.books.len())).collect();
-            format!("Standings: {}", player_books_strings.join(", "))
-        };
-        println!("{}{}", termion::cursor::Goto(1, 3), player_books_string);
+// FIXME: Pass Player structs instead of String
+fn determine_winner<'a>(standings: &'a Vec<&Player>) -> Option<&'a Player> {
+    let mut total_book_count = 0;
 
-        let player_cards_string = player.get_card_labels().join(", ");
-        println!("{}{}",
-            termion::cursor::Goto(1, terminal_info.height / 2),
-            player_cards_string);
+    for &player in standings.iter() {
+        total_book_count = total_book_count + player.books.len();
+    }
 
-        // print status lines
-        for (pos, line) in game_log.status_lines.iter().enumerate() {
-            println!("{}{}",
-                termion::cursor::Goto(1, terminal_info.height - 2 - (pos as u16)),
-                line);
+    if total_book_count >= 13 {
+        return Some(&standings[0]);
+    }
+
+    return None;
+}
+
+fn gameover(name: &str, books: usize) {
+    println!("{}{}{} has won with {} books", termion::clear::All, termion::cursor::Goto(1, 1), name, books);
+}
+
+// Outcome of one game: enough to aggregate stats across a batch, write a
+// `--json-out` transcript, or check a replay's standings match what was
+// recorded.
+struct GameResult {
+    turns: usize,
+    winner_name: String,
+    standings: Vec<(String, usize)>,
+    events: Vec<GameEvent>,
+    // Set when `input` ran dry before all 13 books were formed, e.g. a
+    // --script shorter than the game it was driving.
+    incomplete: bool,
+}
+
+// Plays a complete game, calling `render` before every call to `turn()`
+// (more than once per turn while a seat keeps the turn). This is the one game loop: `main` (interactive), `run_simulation`
+// (headless batches) and `run_replay` (headless verification) all drive it
+// through this function, so every `GameEvent` is recorded the same way no
+// matter who's calling, and a seed reproduces an exact game everywhere.
+fn run_game<R: Rng>(
+    rng: &mut R,
+    mut players: Vec<Player>,
+    deck_config: DeckConfig,
+    input: &mut dyn InputSource,
+    terminal_info: &TerminalInfo,
+    mut render: impl FnMut(&TerminalInfo, &Vec<Card>, &GameLog, &Vec<Player>, usize),
+) -> GameResult {
+    let mut deck = generate_deck(deck_config);
+    assert_eq!(if deck_config == DeckConfig::Jokers { 54 } else { 52 }, deck.len());
+
+    let hand_size: u8 = if players.len() <= 3 { 7 } else { 5 };
+    for player in players.iter_mut() {
+        give_random_card(&mut deck, player, hand_size, rng);
+    }
+
+    let mut game_log = GameLog::new();
+    let mut current_turn_index: usize = 0;
+    let mut turns: usize = 0;
+
+    loop {
+        let mut newly_formed_books: Vec<(usize, u8)> = vec![];
+        for (index, player) in players.iter_mut().enumerate() {
+            for rank in check_cards_for_books(&mut game_log, player) {
+                newly_formed_books.push((index, rank));
+            }
+        }
+
+        for (booked_index, rank) in newly_formed_books {
+            for player in players.iter_mut() {
+                player.knowledge.clear_rank_for_player(booked_index, rank);
+            }
+        }
+
+        let game_standings: Vec<&Player> = {
+            let mut standings: Vec<&Player> = players.iter().collect();
+            standings.sort_by(|a, b| {
+                return a.books.len().cmp(&b.books.len()).reverse().then_with(|| a.name.cmp(&b.name));
+            });
+            standings
+        };
+
+        if let Some(winner) = determine_winner(&game_standings) {
+            let winner_name = winner.name.clone();
+            let standings = game_standings.iter().map(|player| (player.name.clone(), player.books.len())).collect();
+            game_log.add_event(GameEvent::GameOver { winner: winner_name.clone() });
+            return GameResult { turns, winner_name, standings, events: game_log.events, incomplete: false };
         }
 
-        if !player.is_cpu {
+        render(terminal_info, &deck, &game_log, &players, current_turn_index);
+
+        if !players[current_turn_index].is_cpu {
             // clear the last game log
             game_log.clear();
+
+            // An empty-handed human is skipped by `turn()` below without
+            // ever touching `input`, so a drained script shouldn't end the
+            // game here either.
+            if !players[current_turn_index].cards.is_empty() && input.is_exhausted() {
+                let standings = game_standings.iter().map(|player| (player.name.clone(), player.books.len())).collect();
+                return GameResult { turns, winner_name: String::new(), standings, events: game_log.events, incomplete: true };
+            }
         }
 
-        let (next_player, next_opponent, is_next_turn) = turn(
-            &terminal_info,
+        let previous_turn_index = current_turn_index;
+        let (next_players, next_turn_index) = turn(
+            terminal_info,
             &mut game_log,
             &mut deck,
-            player,
-            opponent
+            players,
+            current_turn_index,
+            rng,
+            input,
         );
 
-        player = next_player;
-        opponent = next_opponent;
+        players = next_players;
+        current_turn_index = next_turn_index;
 
-        if is_next_turn {
+        if current_turn_index != previous_turn_index {
             game_log.add_status_line(String::from("End of turn"));
+            game_log.add_event(GameEvent::TurnEnded);
             game_log.turn += 1;
+            turns += 1;
         }
     }
 }
 
-// FIXME: Pass Player structs instead of String
-fn determine_winner<'a>(standings: &'a Vec<&Player>) -> Option<&'a Player> {
-    let mut total_book_count = 0;
-
-    for &player in standings.iter() {
-        total_book_count = total_book_count + player.books.len();
+// Plays `num_games` headless matches of Naive vs. Knowledge CPUs and prints
+// aggregate statistics, so the new AI can be benchmarked against the old.
+fn run_simulation(num_games: usize, seed: Option<u64>, deck_config: DeckConfig) {
+    let mut rng = make_rng(seed);
+    let terminal_info = TerminalInfo { height: 24, width: 80 };
+
+    let strategy_names = ["Naive", "Knowledge"];
+    let mut wins: HashMap<&str, usize> = HashMap::new();
+    let mut total_books: HashMap<&str, usize> = HashMap::new();
+    let mut total_turns: usize = 0;
+
+    for _game_index in 0..num_games {
+        let players = vec![
+            Player::new_cpu_with_strategy("Naive", CpuStrategy::Naive),
+            Player::new_cpu_with_strategy("Knowledge", CpuStrategy::Knowledge),
+        ];
+
+        // Both seats are CPUs, so this input source is never actually read.
+        let result = run_game(&mut rng, players, deck_config, &mut TerminalInput, &terminal_info, |_, _, _, _, _| {});
+
+        total_turns += result.turns;
+        for (name, books) in result.standings.iter() {
+            *total_books.entry(strategy_names.iter().find(|&&s| s == name).unwrap_or(&"Unknown")).or_insert(0) += books;
+        }
+        if let Some(&strategy_name) = strategy_names.iter().find(|&&s| s == result.winner_name) {
+            *wins.entry(strategy_name).or_insert(0) += 1;
+        }
     }
 
-    if total_book_count >= 13 {
-        return Some(&standings[0]);
+    println!("Simulated {} games", num_games);
+    for &strategy_name in strategy_names.iter() {
+        let win_count = *wins.get(strategy_name).unwrap_or(&0);
+        let win_rate = win_count as f64 / num_games as f64 * 100.0;
+        let average_books = *total_books.get(strategy_name).unwrap_or(&0) as f64 / num_games as f64;
+        println!("{}: {} wins ({:.1}%), {:.2} average books per game", strategy_name, win_count, win_rate, average_books);
     }
-
-    return None;
+    println!("Average turns to completion: {:.2}", total_turns as f64 / num_games as f64);
 }
 
-fn gameover(winner: &Player) {
-    println!("{}{}{} has won with {} books", termion::clear::All, termion::cursor::Goto(1, 1), winner.name, winner.books.len());
-}
-
-fn turn(
+fn turn<R: Rng>(
     terminal_info: &TerminalInfo,
     game_log: &mut GameLog,
     mut deck: &mut Vec<Card>,
-    mut current_player: Player,
-    mut current_opponent: Player,
-) -> (Player, Player, bool) {
+    mut players: Vec<Player>,
+    current_index: usize,
+    rng: &mut R,
+    input: &mut dyn InputSource,
+) -> (Vec<Player>, usize) {
+    // A seat holding no cards, or only jokers, has no legal rank to request;
+    // asking anyway would feed a fabricated rank into every player's
+    // Knowledge. Skip straight to the next seat instead.
+    if players[current_index].cards.iter().all(|card| card.is_joker()) {
+        game_log.add_status_line(format!("{} has no rank to ask for and skips their turn", players[current_index].name));
+        let next_index = (current_index + 1) % players.len();
+        return (players, next_index);
+    }
+
     let card_face_value: u8 = {
-        if current_player.is_cpu {
-            let card_value = get_cpu_requesting_card_value(&current_player);
-            game_log.add_status_line(format!("Computer requested {}", card_value));
+        if players[current_index].is_cpu {
+            let card_value = get_cpu_requesting_card_value(&players, current_index);
+            game_log.add_status_line(format!("{} requested {}", players[current_index].name, card_value));
             card_value
         } else {
-            get_player_requesting_card_value(&terminal_info)
+            get_player_requesting_card_value(&terminal_info, input)
         }
     };
 
     if card_face_value < 2 || card_face_value > 14 {
         game_log.add_status_line(String::from("Invalid card face value"));
-        return (current_player, current_opponent, false);
+        return (players, current_index);
     }
 
     let mut has_card: bool = false;
-    for &player_card in current_player.cards.iter() {
+    for &player_card in players[current_index].cards.iter() {
         if player_card.number == card_face_value {
             has_card = true;
             break;
         }
     }
 
-    if !has_card && !current_player.is_cpu {
+    if !has_card && !players[current_index].is_cpu {
         game_log.add_status_line(String::from("You dont have that card"));
-        return (current_player, current_opponent, false);
+        return (players, current_index);
+    }
+
+    // Asking for a rank is public: every player learns the asker holds it.
+    for player in players.iter_mut() {
+        player.knowledge.record_request(current_index, card_face_value);
     }
 
+    let target_index = if players[current_index].is_cpu {
+        get_cpu_requesting_target(&players, current_index, card_face_value)
+    } else {
+        get_player_requesting_target(&terminal_info, &players, current_index, input)
+    };
+
+    game_log.add_event(GameEvent::Request {
+        asker: players[current_index].name.clone(),
+        target: players[target_index].name.clone(),
+        rank: card_face_value,
+    });
+
     let mut found_cards: Vec<Card> = vec![];
 
-    for &other_player_card in current_opponent.cards.iter() {
+    for &other_player_card in players[target_index].cards.iter() {
         if other_player_card.number == card_face_value {
             found_cards.push(other_player_card);
         }
@@ -289,81 +805,148 @@ fn turn(
     if found_cards_length > 0 {
         for card in found_cards.iter() {
             game_log.add_status_line(String::from(format!("{} -{}-> {}",
-                current_opponent.name,
+                players[target_index].name,
                 card.get_label(),
-                current_player.name)));
-            current_player.cards.push(*card);
+                players[current_index].name)));
+            game_log.add_event(GameEvent::Transfer {
+                from: players[target_index].name.clone(),
+                to: players[current_index].name.clone(),
+                card: card.get_label(),
+            });
         }
 
-        found_cards.clear();
+        players[current_index].cards.append(&mut found_cards);
+        players[target_index].cards.retain(|&card| card.number != card_face_value);
+
+        // The target just gave up every copy of this rank they held.
+        for player in players.iter_mut() {
+            player.knowledge.clear_rank_for_player(target_index, card_face_value);
+        }
 
-        current_opponent.cards.retain(|&card| card.number != card_face_value);
-        return (current_player, current_opponent, false);
+        return (players, current_index);
     }
 
     if deck.len() <= 0 {
-        return (current_opponent, current_player, true);
+        game_log.add_event(GameEvent::GoFish {
+            player: players[current_index].name.clone(),
+            drew: None,
+        });
+        let next_index = (current_index + 1) % players.len();
+        return (players, next_index);
     }
 
-    give_random_card(&mut deck, &mut current_player, 1);
+    give_random_card(&mut deck, &mut players[current_index], 1, rng);
 
-    let last_card = current_player.cards.last().unwrap();
+    let last_card = *players[current_index].cards.last().unwrap();
     let mut last_card_label = String::new();
-    if !current_player.is_cpu {
+    if !players[current_index].is_cpu {
         last_card_label = last_card.get_label();
     }
 
+    game_log.add_event(GameEvent::GoFish {
+        player: players[current_index].name.clone(),
+        drew: Some(last_card.get_label()),
+    });
+
     game_log.add_status_line(String::from(format!("Go Fish, deck -{}-> {}",
         last_card_label,
-        current_player.name)));
+        players[current_index].name)));
 
-    return (current_opponent, current_player, true);
+    let next_index = (current_index + 1) % players.len();
+    return (players, next_index);
 }
 
-fn check_cards_for_books(game_log: &mut GameLog, current_player: &mut Player) {
+fn check_cards_for_books(game_log: &mut GameLog, current_player: &mut Player) -> Vec<u8> {
     let mut cards_count: HashMap<u8, u8> = HashMap::new();
+    let mut joker_count: u8 = 0;
 
     for &card in current_player.cards.iter() {
-        *cards_count.entry(card.number).or_insert(0) += 1;
+        if card.is_joker() {
+            joker_count += 1;
+        } else {
+            *cards_count.entry(card.number).or_insert(0) += 1;
+        }
+    }
 
-        if let Some(x) = cards_count.get(&card.number) {
-            if *x >= 4 {
-                // create a book of this.
-                current_player.books.push(Book {
-                    number: card.number,
-                });
+    // Sorted by rank so tie-breaks (which rank a scarce joker completes) are
+    // deterministic; HashMap iteration order isn't.
+    let mut held_vec: Vec<(u8, u8)> = cards_count.into_iter().collect();
+    held_vec.sort_by_key(|&(number, _)| number);
 
-                game_log.add_status_line(String::from(format!("{} collected a book of {}", current_player.name, card.number)));
-            }
-        };
+    let mut booked_ranks: Vec<u8> = vec![];
+    for &(number, count) in held_vec.iter() {
+        if count >= 4 {
+            booked_ranks.push(number);
+        }
+    }
+
+    // A joker is a wildcard that can complete a book of three-of-a-kind.
+    // Allocate each held joker to the rank closest to completion first.
+    let mut wildcard_ranks: Vec<u8> = vec![];
+    let mut jokers_remaining = joker_count;
+    for &(number, count) in held_vec.iter() {
+        if jokers_remaining == 0 {
+            break;
+        }
+        if count == 3 {
+            jokers_remaining -= 1;
+            booked_ranks.push(number);
+            wildcard_ranks.push(number);
+        }
     }
 
-    cards_count.retain(|_k, val| *val >= 4);
+    for &number in booked_ranks.iter() {
+        let used_joker = wildcard_ranks.contains(&number);
+        current_player.books.push(Book { number, used_joker });
+    }
 
-    if cards_count.len() > 0 {
-        // Returning false means the card gets removed.
+    // Read the books back off the player rather than the locals above, so
+    // the rank and joker flag reported here are the ones actually stored.
+    let newly_formed_start = current_player.books.len() - booked_ranks.len();
+    for book in current_player.books[newly_formed_start..].iter() {
+        if book.used_joker {
+            game_log.add_status_line(String::from(format!("{} collected a book of {} (joker wild)", current_player.name, book.number)));
+        } else {
+            game_log.add_status_line(String::from(format!("{} collected a book of {}", current_player.name, book.number)));
+        }
+
+        game_log.add_event(GameEvent::BookFormed {
+            player: current_player.name.clone(),
+            rank: book.number,
+            used_joker: book.used_joker,
+        });
+    }
+
+    if !booked_ranks.is_empty() {
+        let mut jokers_to_remove = wildcard_ranks.len() as u8;
 
+        // Returning false means the card gets removed.
         current_player.cards.retain(|&card| {
-            if cards_count.contains_key(&card.number) {
-                let num_cards = *cards_count.get(&card.number).unwrap();
-                return num_cards < 4; // Below four is allowed.
+            if card.is_joker() {
+                if jokers_to_remove > 0 {
+                    jokers_to_remove -= 1;
+                    return false;
+                }
+                return true;
             }
 
-            return true;
+            !booked_ranks.contains(&card.number)
         });
     }
+
+    booked_ranks
 }
 
 fn get_player_requesting_card_value(
-    terminal_info: &TerminalInfo
+    terminal_info: &TerminalInfo,
+    input: &mut dyn InputSource,
 ) -> u8 {
     println!("{}Enter a card face value to request (0-10, Jack, Queen, King, Ace):",
         termion::cursor::Goto(1, terminal_info.height - 1));
     print!("{}>>> ",
         termion::cursor::Goto(1, terminal_info.height));
 
-    let mut command = String::new();
-    io::stdin().read_line(&mut command).expect("Unable to read line");
+    let command = input.next_token().unwrap_or_default();
     let command = command.trim();
     let command_as_int = match command.parse() {
         Ok(num) => num,
@@ -390,31 +973,114 @@ fn get_player_requesting_card_value(
     };
 }
 
-fn get_cpu_requesting_card_value(player: &Player) -> u8 {
-    let mut cards_count: HashMap<u8, u8> = HashMap::new();
+fn get_player_requesting_target(
+    terminal_info: &TerminalInfo,
+    players: &Vec<Player>,
+    current_index: usize,
+    input: &mut dyn InputSource,
+) -> usize {
+    let opponent_indices: Vec<usize> = (0..players.len()).filter(|&index| index != current_index).collect();
+
+    println!("{}Who do you want to ask?", termion::cursor::Goto(1, terminal_info.height - 1));
+    for (choice, &index) in opponent_indices.iter().enumerate() {
+        println!("{}) {}", choice + 1, players[index].name);
+    }
+    print!("{}>>> ", termion::cursor::Goto(1, terminal_info.height));
+
+    let command = input.next_token().unwrap_or_default();
+    let choice: usize = command.trim_start_matches("target:").parse().unwrap_or(0);
+
+    if choice >= 1 && choice <= opponent_indices.len() {
+        opponent_indices[choice - 1]
+    } else {
+        opponent_indices[0]
+    }
+}
+
+fn get_cpu_requesting_card_value(players: &Vec<Player>, current_index: usize) -> u8 {
+    let player = &players[current_index];
+
+    // Jokers carry no face value, so they can never be requested by rank.
+    let mut held_counts: HashMap<u8, u8> = HashMap::new();
+    for card in player.cards.iter().filter(|card| !card.is_joker()) {
+        *held_counts.entry(card.number).or_insert(0) += 1;
+    }
+
+    // turn() already skips any seat holding no cards or only jokers, so this is
+    // unreachable in practice; kept as a safe default rather than a panic.
+    if held_counts.is_empty() {
+        return 2;
+    }
+
+    // Sorted by rank so tie-breaks are deterministic; HashMap iteration
+    // order isn't, which would otherwise break simulation-mode reproducibility.
+    let mut held_vec: Vec<(u8, u8)> = held_counts.into_iter().collect();
+    held_vec.sort_by_key(|&(rank, _)| rank);
+
+    if player.cpu_strategy == CpuStrategy::Knowledge {
+        // Among ranks we can legally ask for, prefer one a reachable
+        // opponent is known to still hold, maximizing expected cards gained.
+        let mut best_rank: Option<u8> = None;
+        let mut best_known_holders: usize = 0;
+
+        for &(rank, _) in held_vec.iter() {
+            let known_holders = players.iter().enumerate()
+                .filter(|&(index, _)| index != current_index)
+                .filter(|&(index, _)| player.knowledge.holds_rank(index, rank))
+                .count();
+
+            if known_holders > best_known_holders {
+                best_known_holders = known_holders;
+                best_rank = Some(rank);
+            }
+        }
+
+        if let Some(rank) = best_rank {
+            return rank;
+        }
+    }
+
+    // No positive information: fall back to the rank we hold the most of.
+    // The stable sort keeps the rank-order tie-break from above.
+    held_vec.sort_by_key(|&(_, count)| count);
+    held_vec[held_vec.len() - 1].0
+}
 
-    for card in player.cards.iter() {
-        *cards_count.entry(card.number).or_insert(0) += 1;
+fn get_cpu_requesting_target(players: &Vec<Player>, current_index: usize, rank: u8) -> usize {
+    if players[current_index].cpu_strategy == CpuStrategy::Knowledge {
+        // Ask a known holder of this rank if we have one.
+        for (index, _) in players.iter().enumerate() {
+            if index != current_index && players[current_index].knowledge.holds_rank(index, rank) {
+                return index;
+            }
+        }
     }
 
-    let count_vec: Vec<_> = cards_count.into_iter().collect();
-    if count_vec.len() > 0 {
-        let count_value = count_vec[count_vec.len() - 1].0;
+    // No known holder: fall back to the opponent with the most cards.
+    let mut best_index = current_index;
+    let mut best_card_count: i32 = -1;
+
+    for (index, candidate) in players.iter().enumerate() {
+        if index == current_index {
+            continue;
+        }
 
-        return count_value;
+        let card_count = candidate.cards.len() as i32;
+        if card_count > best_card_count {
+            best_card_count = card_count;
+            best_index = index;
+        }
     }
 
-    return 2;
+    best_index
 }
 
-fn give_random_card(
+fn give_random_card<R: Rng>(
     deck: &mut Vec<Card>,
     player: &mut Player,
-    num_cards: u8
+    num_cards: u8,
+    rng: &mut R,
 ) {
-    // this is just a reference and is cached in each thread:
-    let mut rng = rand::thread_rng();
-
     let range = ops::RangeInclusive::new(1, num_cards);
     for _num in range {
         let rand_index = rng.gen_range(0, deck.len());
@@ -424,7 +1090,17 @@ fn give_random_card(
     }
 }
 
-fn generate_deck() -> Vec<Card> {
+// Builds the shared RNG for a game. A seed makes the game fully
+// reproducible (used by simulation mode); without one we seed from OS
+// entropy the same way `rand::thread_rng()` would have.
+fn make_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
+}
+
+fn generate_deck(deck_config: DeckConfig) -> Vec<Card> {
     let all_ranks: [CardRank; 4] = [CardRank::Heart, CardRank::Diamond, CardRank::Spade, CardRank::Clover];
 
     // init the deck
@@ -440,5 +1116,83 @@ fn generate_deck() -> Vec<Card> {
         }
     }
 
+    if deck_config == DeckConfig::Jokers {
+        for _num in 0..2 {
+            deck.push(Card { rank: CardRank::Joker, number: 0 });
+        }
+    }
+
     deck
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A held three-of-a-kind plus a joker should complete a wildcard book
+    // and flag it, proving the allocation logic added for DeckConfig::Jokers
+    // actually forms a book rather than leaving the joker stranded in hand.
+    #[test]
+    fn joker_completes_a_book_and_flags_it() {
+        let mut player = Player::new_cpu("Tester");
+        player.cards = vec![
+            Card { rank: CardRank::Heart, number: 7 },
+            Card { rank: CardRank::Diamond, number: 7 },
+            Card { rank: CardRank::Spade, number: 7 },
+            Card { rank: CardRank::Joker, number: 0 },
+        ];
+
+        let mut game_log = GameLog::new();
+        let booked_ranks = check_cards_for_books(&mut game_log, &mut player);
+
+        assert_eq!(booked_ranks, vec![7]);
+        assert_eq!(player.books.len(), 1);
+        assert_eq!(player.books[0].number, 7);
+        assert!(player.books[0].used_joker);
+        assert!(player.cards.is_empty(), "the used joker and all three sevens should leave the hand");
+    }
+
+    // A Knowledge CPU with positive information about an opponent should
+    // prefer that known-held rank and target over the naive most-held/
+    // most-cards fallback, proving the preference actually branches rather
+    // than just happening to agree with the fallback.
+    #[test]
+    fn knowledge_cpu_prefers_a_known_held_rank_and_target() {
+        let mut asker = Player::new_cpu_with_strategy("Asker", CpuStrategy::Knowledge);
+        asker.cards = vec![
+            Card { rank: CardRank::Heart, number: 4 },
+            Card { rank: CardRank::Heart, number: 4 },
+            Card { rank: CardRank::Heart, number: 4 },
+            Card { rank: CardRank::Heart, number: 9 },
+        ];
+        // Opponent B is the only known holder of rank 9, but Opponent A
+        // holds more cards overall, so a naive fallback would prefer rank 4
+        // (held three times) and Opponent A (more cards) instead.
+        asker.knowledge.record_request(1, 9);
+
+        let mut opponent_a = Player::new_cpu("Opponent A");
+        opponent_a.cards = vec![
+            Card { rank: CardRank::Clover, number: 2 },
+            Card { rank: CardRank::Clover, number: 3 },
+            Card { rank: CardRank::Clover, number: 5 },
+            Card { rank: CardRank::Clover, number: 6 },
+            Card { rank: CardRank::Clover, number: 8 },
+        ];
+
+        let mut opponent_b = Player::new_cpu("Opponent B");
+        opponent_b.cards = vec![
+            Card { rank: CardRank::Spade, number: 9 },
+            Card { rank: CardRank::Spade, number: 10 },
+            Card { rank: CardRank::Spade, number: 11 },
+        ];
+
+        let current_index = 2;
+        let players = vec![opponent_a, opponent_b, asker];
+
+        let card_value = get_cpu_requesting_card_value(&players, current_index);
+        assert_eq!(card_value, 9, "should prefer the known-held rank over the naive most-held rank");
+
+        let target = get_cpu_requesting_target(&players, current_index, card_value);
+        assert_eq!(target, 1, "should target the known holder, not the opponent who merely holds more cards");
+    }
+}
@@ -0,0 +1,74 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+fn bin_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_BIN_EXE_gofish"))
+}
+
+fn temp_file(name: &str) -> PathBuf {
+    let mut path = env::temp_dir();
+    path.push(format!("gofish_test_{}_{}", std::process::id(), name));
+    path
+}
+
+// A move script long enough to guarantee every human turn eventually finds a
+// legal request: a repeated sweep over every rank is certain to hit whatever
+// the human is holding, since a non-empty hand always contains some rank in
+// 2-14.
+fn move_script() -> String {
+    let ranks = ["2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K", "A"];
+    let mut lines = vec![String::from("Tester"), String::from("3"), String::from("n")];
+
+    for _sweep in 0..80 {
+        for rank in ranks.iter() {
+            lines.push(rank.to_string());
+            lines.push(String::from("target:1"));
+            lines.push(rank.to_string());
+            lines.push(String::from("target:2"));
+        }
+    }
+
+    lines.join("\n")
+}
+
+// Drives a full game to completion through `--script`, records it with
+// `--json-out`, then replays the transcript with stdin closed. This is the
+// scenario `--replay` exists for: if the recorded human seat's moves aren't
+// rebuilt from the transcript, this call never finishes: closed stdin gives
+// the human seat no valid request, so its turn repeats without end.
+#[test]
+fn script_driven_game_replays_without_stdin() {
+    let script_path = temp_file("script.txt");
+    let transcript_path = temp_file("transcript.json");
+    fs::write(&script_path, move_script()).expect("Unable to write move script");
+
+    let played = Command::new(bin_path())
+        .arg("--script")
+        .arg(&script_path)
+        .arg("--seed")
+        .arg("5")
+        .arg("--json-out")
+        .arg(&transcript_path)
+        .stdin(Stdio::null())
+        .output()
+        .expect("Unable to run gofish");
+
+    assert!(played.status.success(), "game run did not exit cleanly");
+    assert!(transcript_path.exists(), "game did not finish and write a transcript");
+
+    let replayed = Command::new(bin_path())
+        .arg("--replay")
+        .arg(&transcript_path)
+        .stdin(Stdio::null())
+        .output()
+        .expect("Unable to run gofish --replay");
+
+    assert!(replayed.status.success(), "replay did not exit cleanly");
+    let replayed_stdout = String::from_utf8_lossy(&replayed.stdout);
+    assert!(replayed_stdout.contains("Replay OK"), "replay did not reproduce the recorded standings: {}", replayed_stdout);
+
+    let _ = fs::remove_file(&script_path);
+    let _ = fs::remove_file(&transcript_path);
+}